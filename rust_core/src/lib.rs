@@ -2,18 +2,24 @@ use wasm_bindgen::prelude::*;
 use serde::Serialize;
 use serde::Deserialize;
 use image::{
-    load_from_memory, GrayImage, imageops, GenericImageView, DynamicImage
+    load_from_memory, GrayImage, RgbImage, imageops, GenericImageView, DynamicImage
 };
+use std::collections::HashMap;
 use std::f64::consts::PI;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
-use line_drawing::WalkGrid;
+use svg::node::element::path::Data;
+use svg::node::element::Path;
+use svg::Document;
 
 // --- Global State ---
 // This holds our grayscale image data between function calls.
 // The Mutex ensures we can safely modify it.
 lazy_static! {
     static ref IMAGE_DATA: Mutex<Option<GrayImage>> = Mutex::new(None);
+    static ref COLOR_DATA: Mutex<Option<RgbImage>> = Mutex::new(None); // Color target for multi-color runs
     static ref ORIGINAL_IMAGE: Mutex<Option<DynamicImage>> = Mutex::new(None); // Store original for processing
 }
 
@@ -24,6 +30,28 @@ pub enum Shape {
     Square,
 }
 
+/// Resampling filter used when resizing the source image. Lanczos3 preserves
+/// edge contrast best and is the default for downscaling large photos; Point
+/// is fastest but blockiest.
+#[wasm_bindgen]
+pub enum ResampleFilter {
+    Point,
+    Triangle,
+    CatmullRom,
+    Lanczos3,
+}
+
+impl ResampleFilter {
+    fn to_filter_type(&self) -> imageops::FilterType {
+        match self {
+            ResampleFilter::Point => imageops::FilterType::Nearest,
+            ResampleFilter::Triangle => imageops::FilterType::Triangle,
+            ResampleFilter::CatmullRom => imageops::FilterType::CatmullRom,
+            ResampleFilter::Lanczos3 => imageops::FilterType::Lanczos3,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy)]
 #[wasm_bindgen]
 pub struct Pin {
@@ -31,6 +59,25 @@ pub struct Pin {
     pub y: f64,
 }
 
+/// A single thread color, as an 8-bit sRGB triple.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// The ordered chords laid in one thread color, as returned to JS. Each chord
+/// is a `[from_pin, to_pin]` pair; because the greedy interleaves colors, a
+/// color's chords are not adjacent in the global walk, so the start pin of each
+/// segment must be recorded explicitly rather than inferred from the previous
+/// entry. This lets each color be reconstructed and hung separately.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ColorSequence {
+    pub color: Color,
+    pub segments: Vec<[usize; 2]>,
+}
+
 // --- Public API Functions (callable from JS) ---
 
 #[wasm_bindgen]
@@ -41,6 +88,8 @@ pub fn process_image(
     zoom_level: f64,
     offset_x: f64,
     offset_y: f64,
+    corners: JsValue,
+    filter: Option<ResampleFilter>,
 ) -> Result<(), JsValue> {
     // Attempt to load the original image data if it's not already stored
     if ORIGINAL_IMAGE.lock().is_none() {
@@ -49,8 +98,21 @@ pub fn process_image(
     }
 
     let original_image_guard = ORIGINAL_IMAGE.lock();
-    let original_image = original_image_guard.as_ref().ok_or("Original image not loaded")?;
-    
+    let stored_image = original_image_guard.as_ref().ok_or("Original image not loaded")?;
+
+    // Optional keystone correction: if the user marked the four corners of a
+    // photographed board/frame, warp that quadrilateral back to a rectangle
+    // before anything else downstream sees the image.
+    let corners: Option<[Pin; 4]> = serde_wasm_bindgen::from_value(corners)?;
+    let rectified;
+    let original_image: &DynamicImage = match corners {
+        Some(corners) => {
+            rectified = rectify_perspective(stored_image, corners);
+            &rectified
+        }
+        None => stored_image,
+    };
+
     let original_width = original_image.width() as f64;
     let original_height = original_image.height() as f64;
 
@@ -84,11 +146,16 @@ pub fn process_image(
     );
 
     // Resize the cropped image to fit the canvas dimensions
-    let resized_image = cropped_image.resize_exact(canvas_width, canvas_height, imageops::FilterType::Triangle);
+    // Default to Lanczos3, the best choice for downscaling large photos, when
+    // the caller does not specify a filter.
+    let filter = filter.unwrap_or(ResampleFilter::Lanczos3);
+    let resized_image = cropped_image.resize_exact(canvas_width, canvas_height, filter.to_filter_type());
     
-    // Convert to grayscale and store
+    // Convert to grayscale and store, keeping the color version alongside so
+    // the multi-color search has a faithful target to aim at.
     *IMAGE_DATA.lock() = Some(resized_image.to_luma8());
-    
+    *COLOR_DATA.lock() = Some(resized_image.to_rgb8());
+
     Ok(())
 }
 
@@ -103,67 +170,505 @@ pub fn generate_pins(shape: Shape, num_pins: u32, width: f64, height: f64) -> Re
 }
 
 #[wasm_bindgen]
-pub fn generate_string_art(pins: JsValue, num_lines: u32) -> Result<JsValue, JsValue> {
+pub fn generate_string_art(pins: JsValue, num_lines: u32, line_weight: f64) -> Result<JsValue, JsValue> {
     // 1. Get image and pin data
     let mut image_guard = IMAGE_DATA.lock();
     let image = image_guard.as_mut().ok_or("Image not loaded")?;
     let pins: Vec<Pin> = serde_wasm_bindgen::from_value(pins)?;
-    
+
     if pins.is_empty() {
         return Err("No pins provided".into());
     }
 
+    // A board with fewer than two pins has no chord to draw; return an empty
+    // sequence rather than walking (and cache-indexing) a pin against itself.
+    if pins.len() < 2 {
+        let empty: Vec<usize> = Vec::new();
+        return Ok(serde_wasm_bindgen::to_value(&empty)?);
+    }
+
+    let width = image.width() as i32;
+    let height = image.height() as i32;
+
+    // Precompute each pin-pair's covered pixels (and coverage weights) once,
+    // keyed by the unordered index pair, so the inner loop never re-walks a
+    // line — it is a cheap lookup instead of a fresh allocation.
+    let mut cache: HashMap<(usize, usize), Vec<(usize, f32)>> = HashMap::new();
+    for i in 0..pins.len() {
+        for j in (i + 1)..pins.len() {
+            let mut mask = Vec::new();
+            wu_line(pins[i], pins[j], |px, py, coverage| {
+                if px >= 0 && py >= 0 && px < width && py < height {
+                    mask.push((py as usize * width as usize + px as usize, coverage as f32));
+                }
+            });
+            cache.insert((i, j), mask);
+        }
+    }
+
+    // Work on a flat buffer so the search can read it in parallel and the
+    // erase step can write it by precomputed pixel index.
+    let mut pixels: Vec<u8> = image.as_raw().clone();
+
     // 2. Main algorithm
     let mut line_sequence = Vec::with_capacity(num_lines as usize);
     let mut current_pin_index = 0;
-    
+
     for _ in 0..num_lines {
-        let mut best_next_pin_index = 0;
-        let mut max_score = -1.0; // Use a float for scoring
-        
-        let start_pin = pins[current_pin_index];
+        let start = current_pin_index;
 
-        // Find the best line from the current pin
-        for (i, end_pin) in pins.iter().enumerate() {
-            if i == current_pin_index { continue; } // Don't connect a pin to itself
-
-            let line_pixels = WalkGrid::new((start_pin.x as i32, start_pin.y as i32), (end_pin.x as i32, end_pin.y as i32));
-            let mut current_score = 0.0;
-            
-            for (px, py) in line_pixels {
-                if let Some(pixel) = image.get_pixel_checked(px as u32, py as u32) {
-                    // Invert the value: darker pixels (lower value) give higher score
-                    current_score += 255.0 - pixel[0] as f64;
-                }
+        // Score a candidate end-pin against the current buffer, weighting each
+        // touched pixel by its anti-aliased coverage so diagonal lines are
+        // scored on the same footing as axis-aligned ones.
+        let score = |i: usize| -> f64 {
+            if i == start {
+                return -1.0;
             }
-            
-            if current_score > max_score {
-                max_score = current_score;
-                best_next_pin_index = i;
+            let mask = &cache[&pair_key(start, i)];
+            let mut s = 0.0;
+            for &(idx, coverage) in mask {
+                // Invert the value: darker pixels (lower value) give higher score
+                s += (255.0 - pixels[idx] as f64) * coverage as f64;
             }
-        }
-        
+            s
+        };
+
+        // Parallel argmax over candidate end-pins, falling back to serial when
+        // a thread pool is unavailable.
+        let best = argmax_score(pins.len(), &score);
+        let best_next_pin_index = best.0;
+
         // 3. Update state
         line_sequence.push(best_next_pin_index);
-        
-        // "Erase" the chosen line from the image by making it lighter
-        let best_pin = pins[best_next_pin_index];
-        let line_to_erase = WalkGrid::new((start_pin.x as i32, start_pin.y as i32), (best_pin.x as i32, best_pin.y as i32));
-        for (px, py) in line_to_erase {
-            if let Some(pixel) = image.get_pixel_mut_checked(px as u32, py as u32) {
-                // Add brightness, capping at 255 (white)
-                pixel[0] = (pixel[0] as u16 + 150).min(255) as u8;
-            }
+
+        // "Erase" the chosen line from the buffer by making it lighter, in
+        // proportion to each pixel's coverage, clamping towards white.
+        for &(idx, coverage) in &cache[&pair_key(start, best_next_pin_index)] {
+            let lightened = pixels[idx] as f64 + line_weight * coverage as f64;
+            pixels[idx] = lightened.min(255.0) as u8;
         }
-        
+
         current_pin_index = best_next_pin_index;
     }
 
+    // Persist the lightened buffer back into the stored image.
+    image.copy_from_slice(&pixels);
+
     Ok(serde_wasm_bindgen::to_value(&line_sequence)?)
 }
 
+#[wasm_bindgen]
+pub fn generate_color_string_art(pins: JsValue, num_lines: u32, palette: JsValue) -> Result<JsValue, JsValue> {
+    let pins: Vec<Pin> = serde_wasm_bindgen::from_value(pins)?;
+    let palette: Vec<Color> = serde_wasm_bindgen::from_value(palette)?;
+
+    if pins.is_empty() {
+        return Err("No pins provided".into());
+    }
+    if palette.is_empty() {
+        return Err("No palette colors provided".into());
+    }
+
+    let image_guard = COLOR_DATA.lock();
+    let image = image_guard.as_ref().ok_or("Image not loaded")?;
+    let (img_w, img_h) = (image.width(), image.height());
+
+    // Target image in linear RGB, indexed by pixel.
+    let target: Vec<[f64; 3]> = image
+        .pixels()
+        .map(|p| [srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2])])
+        .collect();
+
+    // Palette colors in linear RGB.
+    let palette_linear: Vec<[f64; 3]> = palette
+        .iter()
+        .map(|c| [srgb_to_linear(c.r), srgb_to_linear(c.g), srgb_to_linear(c.b)])
+        .collect();
+
+    // Single working buffer in linear RGB, starting from white.
+    let mut buf = vec![[1.0_f64; 3]; target.len()];
+
+    let mut sequences: Vec<Vec<[usize; 2]>> = vec![Vec::new(); palette.len()];
+    let mut current_pin_index = 0;
+
+    let index = |x: i32, y: i32| -> Option<usize> {
+        if x < 0 || y < 0 || x as u32 >= img_w || y as u32 >= img_h {
+            None
+        } else {
+            Some(y as usize * img_w as usize + x as usize)
+        }
+    };
+
+    for _ in 0..num_lines {
+        let start_pin = pins[current_pin_index];
+
+        let mut best_improvement = 0.0;
+        let mut best_pin_index = current_pin_index;
+        let mut best_color_index = 0;
+
+        for (i, end_pin) in pins.iter().enumerate() {
+            if i == current_pin_index {
+                continue;
+            }
+
+            // Gather the covered pixels and coverage once per candidate line.
+            let mut covered: Vec<(usize, f64)> = Vec::new();
+            wu_line(start_pin, *end_pin, |px, py, coverage| {
+                if let Some(idx) = index(px, py) {
+                    covered.push((idx, coverage));
+                }
+            });
+
+            // Score each palette color: how much compositing this thread moves
+            // the buffer towards the target (drop in squared residual).
+            for (ci, color) in palette_linear.iter().enumerate() {
+                let mut improvement = 0.0;
+                for &(idx, coverage) in &covered {
+                    let a = coverage.clamp(0.0, 1.0);
+                    let cur = buf[idx];
+                    let tgt = target[idx];
+                    for ch in 0..3 {
+                        let before = tgt[ch] - cur[ch];
+                        let after_val = cur[ch] * (1.0 - a) + color[ch] * a;
+                        let after = tgt[ch] - after_val;
+                        improvement += before * before - after * after;
+                    }
+                }
+                if improvement > best_improvement {
+                    best_improvement = improvement;
+                    best_pin_index = i;
+                    best_color_index = ci;
+                }
+            }
+        }
+
+        // No color/line improves the residual any further; stop early.
+        if best_improvement <= 0.0 {
+            break;
+        }
+
+        // Composite the chosen colored thread into the buffer.
+        let best_pin = pins[best_pin_index];
+        let color = palette_linear[best_color_index];
+        wu_line(start_pin, best_pin, |px, py, coverage| {
+            if let Some(idx) = index(px, py) {
+                let a = coverage.clamp(0.0, 1.0);
+                for ch in 0..3 {
+                    buf[idx][ch] = buf[idx][ch] * (1.0 - a) + color[ch] * a;
+                }
+            }
+        });
+
+        // Record the actual chord (start -> end) so this color's segments are
+        // recoverable independently of the interleaved global walk.
+        sequences[best_color_index].push([current_pin_index, best_pin_index]);
+        current_pin_index = best_pin_index;
+    }
+
+    let result: Vec<ColorSequence> = palette
+        .into_iter()
+        .zip(sequences)
+        .map(|(color, segments)| ColorSequence { color, segments })
+        .collect();
+
+    Ok(serde_wasm_bindgen::to_value(&result)?)
+}
+
+/// Render a walked pin sequence as a standalone SVG document.
+///
+/// `start_pin` is the pin the thread is hung from first, and `line_sequence` is
+/// the ordered end-pins as returned by `generate_string_art` (which omits that
+/// implicit start pin). Passing the start pin explicitly lets the output of
+/// `generate_string_art` be piped straight in without the caller having to
+/// prepend anything, so the opening chord is never dropped.
+#[wasm_bindgen]
+pub fn render_svg(
+    pins: JsValue,
+    start_pin: usize,
+    line_sequence: JsValue,
+    width: f64,
+    height: f64,
+    stroke_width: f64,
+    opacity: f64,
+) -> Result<String, JsValue> {
+    let pins: Vec<Pin> = serde_wasm_bindgen::from_value(pins)?;
+    let line_sequence: Vec<usize> = serde_wasm_bindgen::from_value(line_sequence)?;
+
+    if pins.is_empty() {
+        return Err("No pins provided".into());
+    }
+
+    // Reconstruct the full walk: the start pin followed by each visited end pin.
+    let walk: Vec<usize> = std::iter::once(start_pin)
+        .chain(line_sequence.iter().copied())
+        .collect();
+
+    // Emit one <path> per chord rather than a single combined path: a
+    // self-overlapping single path rasterizes as one shape and composites its
+    // opacity only once, so crossings would not darken. Separate elements let
+    // each chord's stroke-opacity composite on top of the others, so
+    // overlapping strings accumulate darkness the way real thread does.
+    let mut document = Document::new()
+        .set("viewBox", (0.0, 0.0, width, height))
+        .set("width", width)
+        .set("height", height);
+
+    for pair in walk.windows(2) {
+        let from = *pins.get(pair[0]).ok_or("Pin index out of range")?;
+        let to = *pins.get(pair[1]).ok_or("Pin index out of range")?;
+        let data = Data::new()
+            .move_to((from.x, from.y))
+            .line_to((to.x, to.y));
+        let path = Path::new()
+            .set("fill", "none")
+            .set("stroke", "black")
+            .set("stroke-width", stroke_width)
+            .set("stroke-opacity", opacity)
+            .set("d", data);
+        document = document.add(path);
+    }
+
+    Ok(document.to_string())
+}
+
 
 // --- Helper Functions (private) ---
+
+/// Cache key for a pin pair, normalized so the two orderings share an entry.
+fn pair_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Find the candidate end-pin (in `0..num_pins`) with the highest score. Uses a
+/// rayon parallel reduction when the `parallel` feature (and a thread pool) is
+/// available, and falls back to a serial scan otherwise. Returns
+/// `(best_index, best_score)`.
+fn argmax_score<F>(num_pins: usize, score: &F) -> (usize, f64)
+where
+    F: Fn(usize) -> f64 + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        (0..num_pins)
+            .into_par_iter()
+            .map(|i| (i, score(i)))
+            .reduce(|| (0, -1.0), |a, b| if b.1 > a.1 { b } else { a })
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut best = (0, -1.0);
+        for i in 0..num_pins {
+            let s = score(i);
+            if s > best.1 {
+                best = (i, s);
+            }
+        }
+        best
+    }
+}
+
+/// Rectify a trapezoidally-distorted photo by mapping the user-marked
+/// quadrilateral `corners` (top-left, top-right, bottom-right, bottom-left) to
+/// the full output rectangle. The 3x3 homography is solved from the four
+/// corner->corner correspondences (8 unknowns, `h33` fixed to 1); every
+/// destination pixel is then pulled back through that homography and sampled
+/// with bilinear interpolation, leaving out-of-bounds samples white.
+fn rectify_perspective(image: &DynamicImage, corners: [Pin; 4]) -> DynamicImage {
+    let rgba = image.to_rgba8();
+    let (w, h) = (rgba.width(), rgba.height());
+    let (wf, hf) = (w as f64, h as f64);
+
+    // Destination rectangle corners, in the same winding as `corners`.
+    let rect = [
+        Pin { x: 0.0, y: 0.0 },
+        Pin { x: wf, y: 0.0 },
+        Pin { x: wf, y: hf },
+        Pin { x: 0.0, y: hf },
+    ];
+
+    // H maps a destination (rectangle) point to its source (quad) point, so we
+    // can pull each output pixel directly from the original.
+    let homography = compute_homography(rect, corners);
+
+    let mut out = RgbImage::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let (dx, dy) = (x as f64 + 0.5, y as f64 + 0.5);
+            let denom = homography[6] * dx + homography[7] * dy + homography[8];
+            let sx = (homography[0] * dx + homography[1] * dy + homography[2]) / denom;
+            let sy = (homography[3] * dx + homography[4] * dy + homography[5]) / denom;
+
+            out.put_pixel(x, y, image::Rgb(sample_bilinear(&rgba, sx - 0.5, sy - 0.5)));
+        }
+    }
+
+    DynamicImage::ImageRgb8(out)
+}
+
+/// Solve the 3x3 homography (returned row-major, `h33 = 1`) mapping the four
+/// `from` points to the four `to` points via the standard 8x8 linear system.
+fn compute_homography(from: [Pin; 4], to: [Pin; 4]) -> [f64; 9] {
+    let mut a = [[0.0_f64; 9]; 8]; // augmented 8x(8+1)
+    for i in 0..4 {
+        let (x, y) = (from[i].x, from[i].y);
+        let (u, v) = (to[i].x, to[i].y);
+        a[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+        a[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for col in 0..8 {
+        let mut pivot = col;
+        for row in (col + 1)..8 {
+            if a[row][col].abs() > a[pivot][col].abs() {
+                pivot = row;
+            }
+        }
+        a.swap(col, pivot);
+
+        let diag = a[col][col];
+        if diag.abs() < 1e-12 {
+            continue;
+        }
+        for row in 0..8 {
+            if row == col {
+                continue;
+            }
+            let factor = a[row][col] / diag;
+            for k in col..9 {
+                a[row][k] -= factor * a[col][k];
+            }
+        }
+    }
+
+    let mut h = [0.0_f64; 9];
+    for i in 0..8 {
+        let diag = a[i][i];
+        h[i] = if diag.abs() < 1e-12 { 0.0 } else { a[i][8] / diag };
+    }
+    h[8] = 1.0;
+    h
+}
+
+/// Bilinearly sample an RGBA image at a floating-point coordinate, returning
+/// white for samples that fall outside the image.
+fn sample_bilinear(image: &image::RgbaImage, x: f64, y: f64) -> [u8; 3] {
+    let (w, h) = (image.width() as i32, image.height() as i32);
+    let x0 = x.floor() as i32;
+    let y0 = y.floor() as i32;
+    let (x1, y1) = (x0 + 1, y0 + 1);
+
+    if x1 < 0 || y1 < 0 || x0 >= w || y0 >= h {
+        return [255, 255, 255];
+    }
+
+    let fx = x - x0 as f64;
+    let fy = y - y0 as f64;
+
+    let at = |px: i32, py: i32| -> [f64; 3] {
+        if px < 0 || py < 0 || px >= w || py >= h {
+            [255.0, 255.0, 255.0]
+        } else {
+            let p = image.get_pixel(px as u32, py as u32);
+            [p[0] as f64, p[1] as f64, p[2] as f64]
+        }
+    };
+
+    let c00 = at(x0, y0);
+    let c10 = at(x1, y0);
+    let c01 = at(x0, y1);
+    let c11 = at(x1, y1);
+
+    let mut out = [0u8; 3];
+    for ch in 0..3 {
+        let top = c00[ch] * (1.0 - fx) + c10[ch] * fx;
+        let bottom = c01[ch] * (1.0 - fx) + c11[ch] * fx;
+        out[ch] = (top * (1.0 - fy) + bottom * fy).round().clamp(0.0, 255.0) as u8;
+    }
+    out
+}
+
+/// Convert an 8-bit sRGB channel value to its linear-light [0, 1] equivalent,
+/// so that thread colors composite and accumulate the way light actually does.
+fn srgb_to_linear(value: u8) -> f64 {
+    let c = value as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Trace the anti-aliased line between two pins using Xiaolin Wu's algorithm,
+/// invoking `plot(x, y, coverage)` for each touched pixel. The major axis is
+/// stepped one integer unit at a time while the minor coordinate advances by
+/// the slope `gradient`; the fractional part splits coverage between the two
+/// straddling pixels, and the endpoints contribute their partial coverage.
+fn wu_line<F: FnMut(i32, i32, f64)>(start: Pin, end: Pin, mut plot: F) {
+    let (mut x0, mut y0, mut x1, mut y1) = (start.x, start.y, end.x, end.y);
+
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        std::mem::swap(&mut x0, &mut y0);
+        std::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+
+    // Plot a pixel in the original coordinate space, un-swapping the axes.
+    let mut plot_coverage = |x: i32, y: i32, coverage: f64| {
+        if coverage <= 0.0 {
+            return;
+        }
+        if steep {
+            plot(y, x, coverage);
+        } else {
+            plot(x, y, coverage);
+        }
+    };
+
+    let frac = |v: f64| v - v.floor();
+    let rfrac = |v: f64| 1.0 - frac(v);
+
+    // First endpoint.
+    let xend = x0.round();
+    let yend = y0 + gradient * (xend - x0);
+    let xgap = rfrac(x0 + 0.5);
+    let xpxl1 = xend as i32;
+    let ypxl1 = yend.floor() as i32;
+    plot_coverage(xpxl1, ypxl1, rfrac(yend) * xgap);
+    plot_coverage(xpxl1, ypxl1 + 1, frac(yend) * xgap);
+    let mut intery = yend + gradient;
+
+    // Second endpoint.
+    let xend = x1.round();
+    let yend = y1 + gradient * (xend - x1);
+    let xgap = frac(x1 + 0.5);
+    let xpxl2 = xend as i32;
+    let ypxl2 = yend.floor() as i32;
+
+    // Main loop over the major axis.
+    for x in (xpxl1 + 1)..xpxl2 {
+        let y = intery.floor() as i32;
+        plot_coverage(x, y, rfrac(intery));
+        plot_coverage(x, y + 1, frac(intery));
+        intery += gradient;
+    }
+
+    plot_coverage(xpxl2, ypxl2, rfrac(yend) * xgap);
+    plot_coverage(xpxl2, ypxl2 + 1, frac(yend) * xgap);
+}
+
 fn generate_circular_pins(num_pins: u32, width: f64, height: f64) -> Vec<Pin> {
     // ... same code ...
     let mut pins = Vec::new();